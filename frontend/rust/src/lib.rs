@@ -144,8 +144,263 @@ fn extract_domain(url: &str) -> String {
     url.to_string()
 }
 
-// Calculate relevance score for a history entry based on keywords
-fn calculate_relevance_score(entry: &HistoryEntry, keywords: &[String], current_time: f64) -> f64 {
+// Which identity to dedupe entries by: the full canonicalized URL, or just
+// the domain (collapsing every page on a site into one entry).
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupeMode {
+    CanonicalUrl,
+    Domain,
+}
+
+// Tracking query parameters stripped when canonicalizing a URL, so
+// "?utm_source=..." variants of the same page collapse into one entry.
+fn is_tracking_param(key: &str) -> bool {
+    key.starts_with("utm_") || key == "fbclid" || key == "gclid"
+}
+
+// Canonicalizes a URL for deduplication: lowercases the host, strips a
+// leading "www.", drops tracking query parameters, and trims a trailing
+// slash from the path, so cosmetic variants of the same page share a key.
+fn canonicalize_url(url: &str) -> String {
+    let (scheme, rest) = match url.find("://") {
+        Some(idx) => (&url[..idx], &url[idx + 3..]),
+        None => ("", url),
+    };
+
+    let (host_and_path, query) = match rest.find('?') {
+        Some(idx) => (&rest[..idx], Some(&rest[idx + 1..])),
+        None => (rest, None),
+    };
+
+    let (host, path) = match host_and_path.find('/') {
+        Some(idx) => (&host_and_path[..idx], &host_and_path[idx..]),
+        None => (host_and_path, ""),
+    };
+
+    let host_lower = host.to_lowercase();
+    let host_normalized = host_lower.strip_prefix("www.").unwrap_or(&host_lower);
+    let path_normalized = if path == "/" { "" } else { path.trim_end_matches('/') };
+
+    let kept_query: Vec<&str> = query
+        .into_iter()
+        .flat_map(|q| q.split('&'))
+        .filter(|pair| !is_tracking_param(pair.split('=').next().unwrap_or("")))
+        .collect();
+
+    let mut canonical = format!("{}://{}{}", scheme, host_normalized, path_normalized);
+    if !kept_query.is_empty() {
+        canonical.push('?');
+        canonical.push_str(&kept_query.join("&"));
+    }
+    canonical
+}
+
+fn dedupe_key(entry: &HistoryEntry, mode: DedupeMode) -> String {
+    match mode {
+        DedupeMode::CanonicalUrl => canonicalize_url(&entry.url),
+        DedupeMode::Domain => extract_domain(&entry.url).to_lowercase(),
+    }
+}
+
+// Groups `entries` by `dedupe_key`, merging each group onto the
+// representative entry seen first (callers pass already-ranked entries, so
+// that's the highest-scoring one) by summing visit counts and keeping the
+// most recent visit time, while preserving rank order in the output.
+fn deduplicate_history_entries(entries: Vec<HistoryEntry>, mode: DedupeMode) -> Vec<HistoryEntry> {
+    let mut order: Vec<String> = Vec::new();
+    let mut groups: HashMap<String, HistoryEntry> = HashMap::new();
+
+    for entry in entries {
+        let key = dedupe_key(&entry, mode);
+        match groups.get_mut(&key) {
+            Some(existing) => {
+                existing.visit_count += entry.visit_count;
+                if entry.last_visit_time > existing.last_visit_time {
+                    existing.last_visit_time = entry.last_visit_time;
+                }
+            }
+            None => {
+                order.push(key.clone());
+                groups.insert(key, entry);
+            }
+        }
+    }
+
+    order.into_iter().filter_map(|key| groups.remove(&key)).collect()
+}
+
+// Configuration for fuzzy (typo-tolerant) keyword matching. Longer keywords
+// are allowed proportionally more typos, so a short word still has to match
+// closely while a long one can absorb a couple of misspellings.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzyConfig {
+    pub max_typos_short: u32,
+    pub max_typos_medium: u32,
+    pub max_typos_long: u32,
+    pub medium_len_threshold: u32,
+    pub long_len_threshold: u32,
+}
+
+#[wasm_bindgen]
+impl FuzzyConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> FuzzyConfig {
+        FuzzyConfig::default()
+    }
+}
+
+impl Default for FuzzyConfig {
+    fn default() -> Self {
+        FuzzyConfig {
+            max_typos_short: 0,
+            max_typos_medium: 1,
+            max_typos_long: 2,
+            medium_len_threshold: 4,
+            long_len_threshold: 9,
+        }
+    }
+}
+
+impl FuzzyConfig {
+    fn max_typos_for(&self, keyword_len: usize) -> u32 {
+        if keyword_len as u32 >= self.long_len_threshold {
+            self.max_typos_long
+        } else if keyword_len as u32 >= self.medium_len_threshold {
+            self.max_typos_medium
+        } else {
+            self.max_typos_short
+        }
+    }
+}
+
+// Split a lowercased field into word tokens, the same unit fuzzy matching
+// judges typos against (so "pyton" can match the token "python").
+fn tokenize_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_string())
+        .collect()
+}
+
+// Bounded Levenshtein edit distance over a two-row DP table. Bails out as
+// soon as the running minimum of a row exceeds `max_distance`, so words that
+// are clearly too far apart are rejected in O(len) instead of O(n*m).
+fn bounded_levenshtein(a: &str, b: &str, max_distance: u32) -> Option<u32> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let max_distance = max_distance as usize;
+
+    if a.len().abs_diff(b.len()) > max_distance {
+        return None;
+    }
+
+    let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+    let mut curr_row = vec![0usize; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr_row[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            curr_row[j] = (prev_row[j] + 1)
+                .min(curr_row[j - 1] + 1)
+                .min(prev_row[j - 1] + cost);
+        }
+
+        if *curr_row.iter().min().unwrap() > max_distance {
+            return None;
+        }
+
+        std::mem::swap(&mut prev_row, &mut curr_row);
+    }
+
+    let distance = prev_row[b.len()];
+    (distance <= max_distance).then_some(distance as u32)
+}
+
+// Finds the fewest typos needed for `keyword` to match a word in `field_lower`.
+// An exact substring hit always wins with 0 typos; otherwise each token is
+// tried against the bounded Levenshtein budget for the keyword's length.
+fn fuzzy_match_typos(keyword: &str, field_lower: &str, config: &FuzzyConfig) -> Option<u32> {
+    if field_lower.contains(keyword) {
+        return Some(0);
+    }
+
+    let max_typos = config.max_typos_for(keyword.len());
+    if max_typos == 0 {
+        return None;
+    }
+
+    tokenize_words(field_lower)
+        .iter()
+        .filter_map(|token| bounded_levenshtein(keyword, token, max_typos))
+        .min()
+}
+
+// True if `phrase` appears as a contiguous run inside `field_tokens`,
+// i.e. a quoted query span matched word-for-word and in order.
+fn phrase_matches(phrase: &[String], field_tokens: &[String]) -> bool {
+    if phrase.is_empty() || phrase.len() > field_tokens.len() {
+        return false;
+    }
+    field_tokens.windows(phrase.len()).any(|window| window == phrase)
+}
+
+// A parsed search query: quoted spans become ordered phrases that must
+// match contiguously, everything else is tokenized into regular keywords.
+struct ParsedQuery {
+    phrases: Vec<Vec<String>>,
+    keywords: Vec<String>,
+}
+
+// Splits `query` into quoted phrases and ordinary keywords. Quoted spans are
+// tokenized the same way entry fields are, so phrase matching compares like
+// with like. Returns an error if the quotes don't balance.
+fn parse_query(query: &str) -> Result<ParsedQuery, String> {
+    if !query.matches('"').count().is_multiple_of(2) {
+        return Err("Unbalanced quotes in query".to_string());
+    }
+
+    let mut phrases = Vec::new();
+    let mut rest = String::new();
+    let mut in_quotes = false;
+    let mut current = String::new();
+
+    for c in query.chars() {
+        if c == '"' {
+            if in_quotes {
+                let tokens = tokenize_words(&current.to_lowercase());
+                if !tokens.is_empty() {
+                    phrases.push(tokens);
+                }
+                current.clear();
+            }
+            in_quotes = !in_quotes;
+            continue;
+        }
+        if in_quotes {
+            current.push(c);
+        } else {
+            rest.push(c);
+        }
+    }
+
+    let keywords = extract_keywords(&rest);
+    Ok(ParsedQuery { phrases, keywords })
+}
+
+// Calculate relevance score for a history entry based on keywords and
+// quoted phrases. When `fuzzy_config` is `Some`, keywords that don't match
+// exactly may still score via a reduced bonus that shrinks as the typo
+// count grows, so exact hits always outrank fuzzy ones.
+fn calculate_relevance_score(
+    entry: &HistoryEntry,
+    keywords: &[String],
+    phrases: &[Vec<String>],
+    current_time: f64,
+    fuzzy_config: Option<&FuzzyConfig>,
+) -> f64 {
     let mut score = 0.0;
 
     let url_lower = entry.url.to_lowercase();
@@ -153,13 +408,39 @@ fn calculate_relevance_score(entry: &HistoryEntry, keywords: &[String], current_
 
     // Keyword matching scores
     for keyword in keywords {
-        // Title matches are worth more
-        if title_lower.contains(keyword) {
-            score += 3.0;
+        match fuzzy_config {
+            Some(config) => {
+                // Title matches are worth more
+                if let Some(typos) = fuzzy_match_typos(keyword, &title_lower, config) {
+                    score += if typos == 0 { 3.0 } else { 3.0 / (typos as f64 + 2.0) };
+                }
+                // URL matches
+                if let Some(typos) = fuzzy_match_typos(keyword, &url_lower, config) {
+                    score += if typos == 0 { 2.0 } else { 2.0 / (typos as f64 + 2.0) };
+                }
+            }
+            None => {
+                // Title matches are worth more
+                if title_lower.contains(keyword) {
+                    score += 3.0;
+                }
+                // URL matches
+                if url_lower.contains(keyword) {
+                    score += 2.0;
+                }
+            }
         }
-        // URL matches
-        if url_lower.contains(keyword) {
-            score += 2.0;
+    }
+
+    // Phrase matches: a pinned multi-word phrase is a much stronger signal
+    // than any individual keyword, title weighted over url.
+    let title_tokens = tokenize_words(&title_lower);
+    let url_tokens = tokenize_words(&url_lower);
+    for phrase in phrases {
+        if phrase_matches(phrase, &title_tokens) {
+            score += 10.0;
+        } else if phrase_matches(phrase, &url_tokens) {
+            score += 6.0;
         }
     }
 
@@ -182,6 +463,254 @@ fn calculate_relevance_score(entry: &HistoryEntry, keywords: &[String], current_
     score
 }
 
+// Recency bonus for a single sampled visit, Firefox-frecency style: a much
+// steeper age decay than the today/week/month buckets `calculate_relevance_score`
+// uses, so a page visited an hour ago clearly outranks one from last month.
+fn recency_bucket_bonus(days_old: f64) -> f64 {
+    if days_old < 1.0 {
+        100.0
+    } else if days_old < 4.0 {
+        70.0
+    } else if days_old < 14.0 {
+        50.0
+    } else if days_old < 31.0 {
+        30.0
+    } else if days_old < 90.0 {
+        10.0
+    } else {
+        0.0
+    }
+}
+
+// Firefox-style "frecency": samples an entry's visits and scales their
+// recency bonuses by how often the page has been visited overall. We only
+// retain `last_visit_time` (not a full visit history), so it stands in as
+// the entry's one representative sample.
+fn calculate_frecency(entry: &HistoryEntry, current_time: f64) -> f64 {
+    let days_old = (current_time - entry.last_visit_time) / (1000.0 * 60.0 * 60.0 * 24.0);
+    let num_samples = 1.0;
+    let sample_bonus_total = recency_bucket_bonus(days_old);
+
+    (entry.visit_count as f64 * sample_bonus_total / num_samples).round()
+}
+
+// Single field `Ascending`/`Descending` can sort on, for ranking pipelines
+// that just want a plain field sort as one of their criteria.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    VisitCount,
+    LastVisitTime,
+}
+
+// One criterion in an ordered ranking pipeline, modeled on MeiliSearch's
+// ranking rules. `rank_history` applies these as successive tie-breakers —
+// the first criterion is primary, later ones only decide entries still tied
+// under every earlier one — instead of flattening everything into a single
+// score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "field")]
+pub enum RankingCriterion {
+    KeywordScore,
+    VisitCount,
+    Recency,
+    Frecency,
+    DomainBoost,
+    Ascending(SortField),
+    Descending(SortField),
+}
+
+// Default pipeline for `rank_history`: prefer keyword/phrase relevance, then
+// frecency, then plain recency as a final tiebreak.
+const DEFAULT_RANKING_CRITERIA: [RankingCriterion; 3] = [
+    RankingCriterion::KeywordScore,
+    RankingCriterion::Frecency,
+    RankingCriterion::Recency,
+];
+
+// Preset used by `sort_history_by_relevance`: popularity first, then recency.
+const BASIC_RANKING_CRITERIA: [RankingCriterion; 2] =
+    [RankingCriterion::VisitCount, RankingCriterion::Recency];
+
+// Preset used by `sort_by_relevance_with_keywords`: a single blended score,
+// matching that function's historical (pre-pipeline) behavior exactly.
+const KEYWORD_RANKING_CRITERIA: [RankingCriterion; 1] = [RankingCriterion::KeywordScore];
+
+// Per-entry values each `RankingCriterion` compares on. `keyword_score` is
+// the existing blended relevance score (keywords + phrases + visit count +
+// recency) rather than a pure keyword count, so `KeywordScore` reproduces
+// `sort_by_relevance_with_keywords`'s historical ordering exactly when used
+// on its own.
+#[derive(Debug, Clone, Copy)]
+struct CriterionMetrics {
+    keyword_score: f64,
+    visit_count: u32,
+    recency_days: f64,
+    frecency: f64,
+    domain_match: bool,
+    last_visit_time: f64,
+}
+
+fn compute_criterion_metrics(
+    entry: &HistoryEntry,
+    keywords: &[String],
+    phrases: &[Vec<String>],
+    current_time: f64,
+) -> CriterionMetrics {
+    let domain = extract_domain(&entry.url).to_lowercase();
+    let domain_match = !keywords.is_empty() && keywords.iter().any(|keyword| domain.contains(keyword));
+    let days_old = (current_time - entry.last_visit_time) / (1000.0 * 60.0 * 60.0 * 24.0);
+
+    CriterionMetrics {
+        keyword_score: calculate_relevance_score(entry, keywords, phrases, current_time, None),
+        visit_count: entry.visit_count,
+        recency_days: days_old,
+        frecency: calculate_frecency(entry, current_time),
+        domain_match,
+        last_visit_time: entry.last_visit_time,
+    }
+}
+
+fn compare_sort_field(field: SortField, a: &CriterionMetrics, b: &CriterionMetrics) -> std::cmp::Ordering {
+    match field {
+        SortField::VisitCount => a.visit_count.cmp(&b.visit_count),
+        SortField::LastVisitTime => a
+            .last_visit_time
+            .partial_cmp(&b.last_visit_time)
+            .unwrap_or(std::cmp::Ordering::Equal),
+    }
+}
+
+fn compare_criterion(
+    criterion: RankingCriterion,
+    a: &CriterionMetrics,
+    b: &CriterionMetrics,
+) -> std::cmp::Ordering {
+    match criterion {
+        RankingCriterion::KeywordScore => b.keyword_score.partial_cmp(&a.keyword_score).unwrap_or(std::cmp::Ordering::Equal),
+        RankingCriterion::VisitCount => b.visit_count.cmp(&a.visit_count),
+        RankingCriterion::Recency => a
+            .recency_days
+            .partial_cmp(&b.recency_days)
+            .unwrap_or(std::cmp::Ordering::Equal),
+        RankingCriterion::Frecency => b.frecency.partial_cmp(&a.frecency).unwrap_or(std::cmp::Ordering::Equal),
+        // Entries whose domain itself contains a query keyword (e.g.
+        // searching "github" favoring github.com) sort first.
+        RankingCriterion::DomainBoost => b.domain_match.cmp(&a.domain_match),
+        RankingCriterion::Ascending(field) => compare_sort_field(field, a, b),
+        RankingCriterion::Descending(field) => compare_sort_field(field, b, a),
+    }
+}
+
+// Sorts `entries` by `criteria[0]`, then recursively re-sorts each block of
+// entries tied under that criterion by `criteria[1..]`, and so on.
+// Equivalent to a lexicographic multi-key sort, but makes each tie-break
+// step explicit.
+fn rank_by_criteria(
+    mut entries: Vec<(HistoryEntry, CriterionMetrics)>,
+    criteria: &[RankingCriterion],
+) -> Vec<(HistoryEntry, CriterionMetrics)> {
+    let Some((&criterion, rest)) = criteria.split_first() else {
+        return entries;
+    };
+    if entries.len() <= 1 {
+        return entries;
+    }
+
+    entries.sort_by(|a, b| compare_criterion(criterion, &a.1, &b.1));
+
+    let mut result = Vec::with_capacity(entries.len());
+    let mut bucket_start = 0;
+    for i in 1..=entries.len() {
+        let still_tied = i < entries.len()
+            && compare_criterion(criterion, &entries[i].1, &entries[bucket_start].1) == std::cmp::Ordering::Equal;
+        if still_tied {
+            continue;
+        }
+        let bucket: Vec<(HistoryEntry, CriterionMetrics)> = entries[bucket_start..i].to_vec();
+        result.extend(rank_by_criteria(bucket, rest));
+        bucket_start = i;
+    }
+    result
+}
+
+fn rank_entries_by_criteria(
+    entries: Vec<HistoryEntry>,
+    criteria: &[RankingCriterion],
+    keywords: &[String],
+    phrases: &[Vec<String>],
+    current_time: f64,
+) -> Vec<HistoryEntry> {
+    let candidates: Vec<(HistoryEntry, CriterionMetrics)> = entries
+        .into_iter()
+        .map(|entry| {
+            let metrics = compute_criterion_metrics(&entry, keywords, phrases, current_time);
+            (entry, metrics)
+        })
+        .collect();
+
+    rank_by_criteria(candidates, criteria)
+        .into_iter()
+        .map(|(entry, _)| entry)
+        .collect()
+}
+
+// General ranking engine: optionally filters entries by quoted phrases and
+// keywords (same rules as `find_relevant_history`), then orders the result
+// through `criteria` as an ordered tie-break chain. An empty `criteria` list
+// falls back to `DEFAULT_RANKING_CRITERIA` so callers don't need to know the
+// criterion names just to get a sensible ranking.
+#[wasm_bindgen]
+pub fn rank_history(
+    entries: JsValue,
+    query: &str,
+    max_results: usize,
+    current_time: f64,
+    criteria: JsValue,
+) -> Result<JsValue, JsValue> {
+    let entries = deserialize_entries(entries).map_err(|e| JsValue::from_str(&e))?;
+
+    let criteria: Vec<RankingCriterion> = if criteria.is_undefined() || criteria.is_null() {
+        Vec::new()
+    } else {
+        serde_wasm_bindgen::from_value(criteria)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse ranking criteria: {}", e)))?
+    };
+    let criteria: &[RankingCriterion] = if criteria.is_empty() { &DEFAULT_RANKING_CRITERIA } else { &criteria };
+
+    let parsed = parse_query(query).map_err(|e| JsValue::from_str(&e))?;
+
+    let candidates = if parsed.keywords.is_empty() && parsed.phrases.is_empty() {
+        entries
+    } else {
+        entries
+            .into_iter()
+            .filter(|entry| {
+                let url_lower = entry.url.to_lowercase();
+                let title_lower = entry.title.to_lowercase();
+                let title_tokens = tokenize_words(&title_lower);
+                let url_tokens = tokenize_words(&url_lower);
+                let phrases_ok = parsed.phrases.iter().all(|phrase| {
+                    phrase_matches(phrase, &title_tokens) || phrase_matches(phrase, &url_tokens)
+                });
+                if !phrases_ok {
+                    return false;
+                }
+                parsed.keywords.is_empty()
+                    || parsed.keywords.iter().any(|keyword| {
+                        url_lower.contains(keyword) || title_lower.contains(keyword)
+                    })
+            })
+            .collect()
+    };
+
+    let ranked = rank_entries_by_criteria(candidates, criteria, &parsed.keywords, &parsed.phrases, current_time);
+    let limited: Vec<HistoryEntry> = ranked.into_iter().take(max_results).collect();
+
+    serde_wasm_bindgen::to_value(&limited)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
 // Basic filtering functions
 
 #[wasm_bindgen]
@@ -227,19 +756,49 @@ pub fn filter_history_by_keywords(
         .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
 
+// Typo-tolerant counterpart of `filter_history_by_keywords`, using
+// `FuzzyConfig` to decide how many typos a keyword may have (defaults used
+// when `config` is `None`).
+#[wasm_bindgen]
+pub fn filter_history_by_keywords_fuzzy(
+    entries: JsValue,
+    keywords: Vec<String>,
+    config: Option<FuzzyConfig>,
+) -> Result<JsValue, JsValue> {
+    let entries = deserialize_entries(entries)
+        .map_err(|e| JsValue::from_str(&e))?;
+
+    let keywords_lower: Vec<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
+    let config = config.unwrap_or_default();
+
+    let filtered: Vec<HistoryEntry> = entries
+        .into_iter()
+        .filter(|entry| {
+            let url_lower = entry.url.to_lowercase();
+            let title_lower = entry.title.to_lowercase();
+            keywords_lower.iter().any(|keyword| {
+                fuzzy_match_typos(keyword, &title_lower, &config).is_some()
+                    || fuzzy_match_typos(keyword, &url_lower, &config).is_some()
+            })
+        })
+        .collect();
+
+    serde_wasm_bindgen::to_value(&filtered)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
 #[wasm_bindgen]
 pub fn sort_history_by_relevance(entries: JsValue) -> Result<JsValue, JsValue> {
-    let mut entries = deserialize_entries(entries)
+    let entries = deserialize_entries(entries)
         .map_err(|e| JsValue::from_str(&e))?;
 
-    // Sort by visit count (descending) and then by last visit time (descending)
-    entries.sort_by(|a, b| {
-        b.visit_count
-            .cmp(&a.visit_count)
-            .then_with(|| b.last_visit_time.partial_cmp(&a.last_visit_time).unwrap_or(std::cmp::Ordering::Equal))
-    });
+    // Thin preset over `rank_history`'s engine: visit count (descending),
+    // then recency (descending). current_time is unused here — recency is
+    // only compared relative to other entries, so its value doesn't affect
+    // ordering.
+    let sorted = rank_entries_by_criteria(entries, &BASIC_RANKING_CRITERIA, &[], &[], 0.0);
 
-    serde_wasm_bindgen::to_value(&entries)
+    serde_wasm_bindgen::to_value(&sorted)
         .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
 
@@ -255,30 +814,45 @@ pub fn sort_by_relevance_with_keywords(
 
     let keywords_lower: Vec<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
 
-    // Calculate scores for all entries
-    let mut scored_entries: Vec<(HistoryEntry, f64)> = entries
-        .into_iter()
-        .map(|entry| {
-            let score = calculate_relevance_score(&entry, &keywords_lower, current_time);
-            (entry, score)
-        })
-        .collect();
+    // Thin preset over `rank_history`'s engine: a single blended score,
+    // matching this function's historical (pre-pipeline) behavior.
+    let sorted = rank_entries_by_criteria(entries, &KEYWORD_RANKING_CRITERIA, &keywords_lower, &[], current_time);
 
-    // Sort by score (descending)
-    scored_entries.sort_by(|a, b| {
-        b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)
-    });
+    serde_wasm_bindgen::to_value(&sorted)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
 
-    // Extract entries
-    let sorted: Vec<HistoryEntry> = scored_entries
-        .into_iter()
-        .map(|(entry, _)| entry)
-        .collect();
+// Advanced: Sort by Firefox-style frecency instead of the coarse
+// today/week/month recency buckets `calculate_relevance_score` uses.
+#[wasm_bindgen]
+pub fn sort_history_by_frecency(entries: JsValue, current_time: f64) -> Result<JsValue, JsValue> {
+    let entries = deserialize_entries(entries)
+        .map_err(|e| JsValue::from_str(&e))?;
+
+    let sorted = sort_entries_by_frecency_internal(entries, current_time);
 
     serde_wasm_bindgen::to_value(&sorted)
         .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
 
+// Collapses near-duplicate history entries (tracking-param, www., and
+// trailing-slash variants of the same page) down to one entry per `mode`'s
+// notion of identity. Since no query/keyword score is available here, the
+// representative kept per group is the one with the highest visit count
+// (basic relevance sort), with visit counts summed and the most recent
+// visit time kept across the group.
+#[wasm_bindgen]
+pub fn deduplicate_history(entries: JsValue, mode: DedupeMode) -> Result<JsValue, JsValue> {
+    let entries = deserialize_entries(entries)
+        .map_err(|e| JsValue::from_str(&e))?;
+
+    let ranked = sort_entries_by_relevance_internal(entries);
+    let deduped = deduplicate_history_entries(ranked, mode);
+
+    serde_wasm_bindgen::to_value(&deduped)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
 #[wasm_bindgen]
 pub fn limit_history_results(entries: JsValue, max_count: usize) -> Result<JsValue, JsValue> {
     let entries = deserialize_entries(entries)
@@ -370,6 +944,120 @@ pub fn analyze_domain_patterns(entries: JsValue) -> Result<JsValue, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
 
+// A dimension `compute_facets` can bucket entries by, so a filtering UI can
+// render counts per domain/TLD/day/hour/age without re-fetching history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Facet {
+    Domain,
+    Tld,
+    DayOfWeek,
+    HourOfDay,
+    AgeBucket,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FacetBucket {
+    key: String,
+    entry_count: u32,
+    total_visits: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FacetResult {
+    facet: Facet,
+    buckets: Vec<FacetBucket>,
+}
+
+fn weekday_label(weekday: chrono::Weekday) -> &'static str {
+    match weekday {
+        chrono::Weekday::Mon => "monday",
+        chrono::Weekday::Tue => "tuesday",
+        chrono::Weekday::Wed => "wednesday",
+        chrono::Weekday::Thu => "thursday",
+        chrono::Weekday::Fri => "friday",
+        chrono::Weekday::Sat => "saturday",
+        chrono::Weekday::Sun => "sunday",
+    }
+}
+
+// Bucket key an entry falls into for a given `Facet`. `DayOfWeek`/`HourOfDay`
+// are derived from `last_visit_time` itself (when the visit happened);
+// `AgeBucket` is relative to `current_time` (how long ago that was), using
+// the same today/week/month buckets as `calculate_relevance_score`.
+fn facet_key(entry: &HistoryEntry, facet: Facet, current_time: f64) -> String {
+    match facet {
+        Facet::Domain => extract_domain(&entry.url).to_lowercase(),
+        Facet::Tld => {
+            let domain = extract_domain(&entry.url).to_lowercase();
+            domain.rsplit('.').next().unwrap_or(&domain).to_string()
+        }
+        Facet::DayOfWeek => match chrono::DateTime::from_timestamp_millis(entry.last_visit_time as i64) {
+            Some(dt) => weekday_label(chrono::Datelike::weekday(&dt)).to_string(),
+            None => "unknown".to_string(),
+        },
+        Facet::HourOfDay => match chrono::DateTime::from_timestamp_millis(entry.last_visit_time as i64) {
+            Some(dt) => format!("{:02}", chrono::Timelike::hour(&dt)),
+            None => "unknown".to_string(),
+        },
+        Facet::AgeBucket => {
+            let days_old = (current_time - entry.last_visit_time) / (1000.0 * 60.0 * 60.0 * 24.0);
+            let bucket = if days_old < 1.0 {
+                "today"
+            } else if days_old < 7.0 {
+                "this_week"
+            } else if days_old < 30.0 {
+                "this_month"
+            } else {
+                "older"
+            };
+            bucket.to_string()
+        }
+    }
+}
+
+// Faceted domain/time distribution, extending `analyze_domain_patterns`'s
+// top-20-domains view into general-purpose faceting: for each requested
+// `Facet`, groups entries into buckets and reports entry/visit counts per
+// bucket, sorted by visit count (descending), so a filtering UI can render
+// chips like "show only github.com" or "only weekend browsing".
+#[wasm_bindgen]
+pub fn compute_facets(entries: JsValue, facets: JsValue, current_time: f64) -> Result<JsValue, JsValue> {
+    let entries = deserialize_entries(entries)
+        .map_err(|e| JsValue::from_str(&e))?;
+
+    let facets: Vec<Facet> = serde_wasm_bindgen::from_value(facets)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse facets: {}", e)))?;
+
+    let results: Vec<FacetResult> = facets
+        .into_iter()
+        .map(|facet| {
+            let mut bucket_stats: HashMap<String, (u32, u32)> = HashMap::new();
+            for entry in &entries {
+                let key = facet_key(entry, facet, current_time);
+                let stats = bucket_stats.entry(key).or_insert((0, 0));
+                stats.0 += 1;
+                stats.1 += entry.visit_count;
+            }
+
+            let mut buckets: Vec<FacetBucket> = bucket_stats
+                .into_iter()
+                .map(|(key, (entry_count, total_visits))| FacetBucket {
+                    key,
+                    entry_count,
+                    total_visits,
+                })
+                .collect();
+            buckets.sort_by(|a, b| b.total_visits.cmp(&a.total_visits));
+
+            FacetResult { facet, buckets }
+        })
+        .collect();
+
+    serde_wasm_bindgen::to_value(&results)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
 // Internal helper: sort entries by relevance (visit count, then recency)
 fn sort_entries_by_relevance_internal(mut entries: Vec<HistoryEntry>) -> Vec<HistoryEntry> {
     entries.sort_by(|a, b| {
@@ -384,24 +1072,25 @@ fn sort_entries_by_relevance_internal(mut entries: Vec<HistoryEntry>) -> Vec<His
 fn sort_entries_by_relevance_with_keywords_internal(
     entries: Vec<HistoryEntry>,
     keywords: &[String],
+    phrases: &[Vec<String>],
     current_time: f64,
 ) -> Vec<HistoryEntry> {
     let keywords_lower: Vec<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
-    
+
     // Calculate scores for all entries
     let mut scored_entries: Vec<(HistoryEntry, f64)> = entries
         .into_iter()
         .map(|entry| {
-            let score = calculate_relevance_score(&entry, &keywords_lower, current_time);
+            let score = calculate_relevance_score(&entry, &keywords_lower, phrases, current_time, None);
             (entry, score)
         })
         .collect();
-    
+
     // Sort by score (descending)
     scored_entries.sort_by(|a, b| {
         b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)
     });
-    
+
     // Extract entries
     scored_entries
         .into_iter()
@@ -409,7 +1098,55 @@ fn sort_entries_by_relevance_with_keywords_internal(
         .collect()
 }
 
-// Combined query function: filter, score, and sort
+// Internal helper: sort entries by relevance with fuzzy (typo-tolerant)
+// keyword matching
+fn sort_entries_by_relevance_fuzzy_internal(
+    entries: Vec<HistoryEntry>,
+    keywords: &[String],
+    current_time: f64,
+    config: &FuzzyConfig,
+) -> Vec<HistoryEntry> {
+    let mut scored_entries: Vec<(HistoryEntry, f64)> = entries
+        .into_iter()
+        .map(|entry| {
+            let score = calculate_relevance_score(&entry, keywords, &[], current_time, Some(config));
+            (entry, score)
+        })
+        .collect();
+
+    scored_entries.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    scored_entries
+        .into_iter()
+        .map(|(entry, _)| entry)
+        .collect()
+}
+
+// Internal helper: sort entries by Firefox-style frecency
+fn sort_entries_by_frecency_internal(entries: Vec<HistoryEntry>, current_time: f64) -> Vec<HistoryEntry> {
+    let mut scored_entries: Vec<(HistoryEntry, f64)> = entries
+        .into_iter()
+        .map(|entry| {
+            let score = calculate_frecency(&entry, current_time);
+            (entry, score)
+        })
+        .collect();
+
+    scored_entries.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    scored_entries
+        .into_iter()
+        .map(|(entry, _)| entry)
+        .collect()
+}
+
+// Combined query function: filter, score, and sort. Recognizes
+// double-quoted spans in `query` as ordered phrases that must match
+// contiguously; returns an error if the quotes in `query` are unbalanced.
 // OPTIMIZED: Avoids unnecessary serializations/deserializations to prevent memory issues
 #[wasm_bindgen]
 pub fn find_relevant_history(
@@ -417,7 +1154,11 @@ pub fn find_relevant_history(
     query: &str,
     max_results: usize,
     current_time: f64,
+    use_frecency: Option<bool>,
+    dedupe_mode: Option<DedupeMode>,
 ) -> Result<JsValue, JsValue> {
+    let use_frecency = use_frecency.unwrap_or(false);
+
     // Add safety check for query string length
     if query.len() > 1000 {
         return Err(JsValue::from_str("Query string too long (max 1000 characters)"));
@@ -464,17 +1205,28 @@ pub fn find_relevant_history(
             .map_err(|e| JsValue::from_str(&format!("Failed to serialize empty result: {}", e)))?);
     }
 
-    // Extract keywords from query
-    let keywords = extract_keywords(query);
+    // Extract quoted phrases and plain keywords from query
+    let parsed = parse_query(query).map_err(|e| JsValue::from_str(&e))?;
 
-    let result: Vec<HistoryEntry> = if keywords.is_empty() {
-        // No keywords, just sort by recency and visit count
+    let result: Vec<HistoryEntry> = if parsed.keywords.is_empty() && parsed.phrases.is_empty() {
+        // No keywords or phrases, just sort by recency and visit count (or frecency)
         // Use internal function to avoid serialization
-        let sorted = sort_entries_by_relevance_internal(entries);
+        let sorted = if use_frecency {
+            sort_entries_by_frecency_internal(entries, current_time)
+        } else {
+            sort_entries_by_relevance_internal(entries)
+        };
+        // Collapse near-duplicate pages before truncating to max_results, so
+        // duplicates don't crowd out distinct results
+        let sorted = match dedupe_mode {
+            Some(mode) => deduplicate_history_entries(sorted, mode),
+            None => sorted,
+        };
         // Limit results
         sorted.into_iter().take(max_results).collect()
     } else {
-        // Filter entries that match keywords
+        // Filter entries that satisfy every quoted phrase and match at
+        // least one plain keyword (if any keywords are present)
         // Note: Filtering first before scoring reduces memory usage
         let filtered: Vec<HistoryEntry> = entries
             .into_iter()
@@ -483,12 +1235,23 @@ pub fn find_relevant_history(
                 if entry.url.is_empty() || entry.last_visit_time <= 0.0 {
                     return false;
                 }
-                
+
                 let url_lower = entry.url.to_lowercase();
                 let title_lower = entry.title.to_lowercase();
-                keywords.iter().any(|keyword| {
-                    url_lower.contains(keyword) || title_lower.contains(keyword)
-                })
+
+                let title_tokens = tokenize_words(&title_lower);
+                let url_tokens = tokenize_words(&url_lower);
+                let phrases_ok = parsed.phrases.iter().all(|phrase| {
+                    phrase_matches(phrase, &title_tokens) || phrase_matches(phrase, &url_tokens)
+                });
+                if !phrases_ok {
+                    return false;
+                }
+
+                parsed.keywords.is_empty()
+                    || parsed.keywords.iter().any(|keyword| {
+                        url_lower.contains(keyword) || title_lower.contains(keyword)
+                    })
             })
             .collect();
 
@@ -505,17 +1268,122 @@ pub fn find_relevant_history(
         };
 
         // Score and sort using internal function to avoid serialization
-        let sorted = sort_entries_by_relevance_with_keywords_internal(
+        let sorted = if use_frecency {
+            sort_entries_by_frecency_internal(filtered_for_scoring, current_time)
+        } else {
+            sort_entries_by_relevance_with_keywords_internal(
+                filtered_for_scoring,
+                &parsed.keywords,
+                &parsed.phrases,
+                current_time,
+            )
+        };
+        let sorted = match dedupe_mode {
+            Some(mode) => deduplicate_history_entries(sorted, mode),
+            None => sorted,
+        };
+
+        // Limit results
+        sorted.into_iter().take(max_results).collect()
+    };
+
+    // Only serialize once at the end
+    serde_wasm_bindgen::to_value(&result)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
+// Typo-tolerant counterpart of `find_relevant_history`, using `FuzzyConfig`
+// to decide how many typos a keyword may have (defaults used when `config`
+// is `None`). Shares the same validation and memory-safety guards as
+// `find_relevant_history`.
+#[wasm_bindgen]
+pub fn find_relevant_history_fuzzy(
+    entries: JsValue,
+    query: &str,
+    max_results: usize,
+    current_time: f64,
+    config: Option<FuzzyConfig>,
+) -> Result<JsValue, JsValue> {
+    if query.len() > 1000 {
+        return Err(JsValue::from_str("Query string too long (max 1000 characters)"));
+    }
+
+    let mut entries: Vec<HistoryEntry> = match deserialize_entries(entries) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return Err(JsValue::from_str(&format!(
+                "{}. Ensure entries have: url (string), title (string), visit_count (integer), last_visit_time (number).",
+                e
+            )));
+        }
+    };
+
+    entries.retain(|entry| {
+        !entry.url.is_empty()
+            && entry.last_visit_time > 0.0
+            && entry.last_visit_time.is_finite()
+            && entry.url.len() < 10000
+            && entry.title.len() < 10000
+    });
+
+    const MAX_ENTRIES: usize = 2000;
+    if entries.len() > MAX_ENTRIES {
+        entries.sort_by(|a, b| {
+            b.last_visit_time.partial_cmp(&a.last_visit_time)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        entries.truncate(MAX_ENTRIES);
+    }
+
+    if entries.is_empty() {
+        return Ok(serde_wasm_bindgen::to_value(&Vec::<HistoryEntry>::new())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize empty result: {}", e)))?);
+    }
+
+    let keywords = extract_keywords(query);
+
+    let result: Vec<HistoryEntry> = if keywords.is_empty() {
+        let sorted = sort_entries_by_relevance_internal(entries);
+        sorted.into_iter().take(max_results).collect()
+    } else {
+        let config = config.unwrap_or_default();
+
+        let filtered: Vec<HistoryEntry> = entries
+            .into_iter()
+            .filter(|entry| {
+                if entry.url.is_empty() || entry.last_visit_time <= 0.0 {
+                    return false;
+                }
+
+                let url_lower = entry.url.to_lowercase();
+                let title_lower = entry.title.to_lowercase();
+                keywords.iter().any(|keyword| {
+                    fuzzy_match_typos(keyword, &title_lower, &config).is_some()
+                        || fuzzy_match_typos(keyword, &url_lower, &config).is_some()
+                })
+            })
+            .collect();
+
+        let filtered_for_scoring = if filtered.len() > 10000 {
+            let mut temp = filtered;
+            temp.sort_by(|a, b| {
+                b.last_visit_time.partial_cmp(&a.last_visit_time).unwrap_or(std::cmp::Ordering::Equal)
+            });
+            temp.into_iter().take(10000).collect()
+        } else {
+            filtered
+        };
+
+        let sorted = sort_entries_by_relevance_fuzzy_internal(
             filtered_for_scoring,
             &keywords,
             current_time,
+            &config,
         );
-        
-        // Limit results
+
         sorted.into_iter().take(max_results).collect()
     };
 
-    // Only serialize once at the end
     serde_wasm_bindgen::to_value(&result)
         .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
@@ -569,7 +1437,164 @@ mod tests {
             1234567890000.0,
         );
         let keywords = vec!["rust".to_string(), "programming".to_string()];
-        let score = calculate_relevance_score(&entry, &keywords, 1234567890000.0);
+        let score = calculate_relevance_score(&entry, &keywords, &[], 1234567890000.0, None);
         assert!(score > 0.0);
     }
+
+    #[test]
+    fn test_calculate_frecency_rewards_recent_and_frequent_visits() {
+        let current_time = 1_000_000_000_000.0;
+        let one_hour_ago = current_time - 60.0 * 60.0 * 1000.0;
+        let ninety_days_ago = current_time - 95.0 * 24.0 * 60.0 * 60.0 * 1000.0;
+
+        let recent = HistoryEntry::new("https://a.com".to_string(), "A".to_string(), 5, one_hour_ago);
+        let stale = HistoryEntry::new("https://b.com".to_string(), "B".to_string(), 5, ninety_days_ago);
+
+        assert!(calculate_frecency(&recent, current_time) > calculate_frecency(&stale, current_time));
+        assert_eq!(calculate_frecency(&stale, current_time), 0.0);
+    }
+
+    #[test]
+    fn test_bounded_levenshtein() {
+        assert_eq!(bounded_levenshtein("python", "python", 2), Some(0));
+        assert_eq!(bounded_levenshtein("python", "pyhton", 2), Some(2));
+        assert_eq!(bounded_levenshtein("python", "java", 2), None);
+    }
+
+    #[test]
+    fn test_fuzzy_match_typos_allows_scaled_typo_budget() {
+        let config = FuzzyConfig::default();
+        assert_eq!(fuzzy_match_typos("javascript", "a page about javascrpt basics", &config), Some(1));
+        assert_eq!(fuzzy_match_typos("cat", "a page about bat", &config), None);
+    }
+
+    #[test]
+    fn test_fuzzy_config_default_allows_one_typo_for_four_char_words() {
+        // Spec: 0 typos for <=3 chars, 1 typo for 4-8 chars, 2 typos for >=9 chars.
+        let config = FuzzyConfig::default();
+        assert_eq!(config.max_typos_for(4), 1);
+        assert_eq!(fuzzy_match_typos("java", "a page about jsva basics", &config), Some(1));
+    }
+
+    #[test]
+    fn test_parse_query_phrase() {
+        let parsed = parse_query("\"rust async\" tutorial").unwrap();
+        assert_eq!(parsed.phrases, vec![vec!["rust".to_string(), "async".to_string()]]);
+        assert_eq!(parsed.keywords, vec!["tutorial".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_query_unbalanced_quotes() {
+        assert!(parse_query("\"rust async book").is_err());
+    }
+
+    #[test]
+    fn test_phrase_matches_requires_contiguous_order() {
+        let phrase = vec!["rust".to_string(), "async".to_string()];
+        assert!(phrase_matches(&phrase, &tokenize_words("a rust async book")));
+        assert!(!phrase_matches(&phrase, &tokenize_words("async rust book")));
+    }
+
+    #[test]
+    fn test_canonicalize_url_strips_www_and_tracking_params() {
+        assert_eq!(
+            canonicalize_url("https://www.EXAMPLE.com/foo/?utm_source=x&ref=y"),
+            "https://example.com/foo?ref=y"
+        );
+        assert_eq!(canonicalize_url("https://example.com"), "https://example.com");
+    }
+
+    #[test]
+    fn test_deduplicate_history_entries_merges_visit_counts() {
+        let entries = vec![
+            HistoryEntry::new("https://example.com/".to_string(), "Example".to_string(), 3, 2000.0),
+            HistoryEntry::new("https://www.example.com".to_string(), "Example".to_string(), 2, 1000.0),
+            HistoryEntry::new("https://other.com".to_string(), "Other".to_string(), 1, 500.0),
+        ];
+
+        let deduped = deduplicate_history_entries(entries, DedupeMode::CanonicalUrl);
+
+        assert_eq!(deduped.len(), 2);
+        assert_eq!(deduped[0].visit_count, 5);
+        assert_eq!(deduped[0].last_visit_time, 2000.0);
+    }
+
+    #[test]
+    fn test_rank_by_criteria_breaks_ties_in_order() {
+        let entries = vec![
+            (
+                HistoryEntry::new("https://a.com".to_string(), "A".to_string(), 5, 100.0),
+                CriterionMetrics {
+                    keyword_score: 0.0,
+                    visit_count: 5,
+                    recency_days: 2.0,
+                    frecency: 0.0,
+                    domain_match: false,
+                    last_visit_time: 100.0,
+                },
+            ),
+            (
+                HistoryEntry::new("https://b.com".to_string(), "B".to_string(), 10, 50.0),
+                CriterionMetrics {
+                    keyword_score: 0.0,
+                    visit_count: 10,
+                    recency_days: 5.0,
+                    frecency: 0.0,
+                    domain_match: false,
+                    last_visit_time: 50.0,
+                },
+            ),
+        ];
+
+        let ranked = rank_by_criteria(entries, &[RankingCriterion::VisitCount, RankingCriterion::Recency]);
+        assert_eq!(ranked[0].0.url, "https://b.com");
+    }
+
+    #[test]
+    fn test_rank_history_respects_domain_boost() {
+        let entries = vec![
+            HistoryEntry::new("https://github.com/rust-lang".to_string(), "Rust Lang".to_string(), 1, 1000.0),
+            HistoryEntry::new("https://blog.example.com/rust".to_string(), "Rust post".to_string(), 50, 1000.0),
+        ];
+
+        let ranked = rank_entries_by_criteria(
+            entries,
+            &[RankingCriterion::DomainBoost],
+            &["github".to_string()],
+            &[],
+            1000.0,
+        );
+        assert_eq!(ranked[0].url, "https://github.com/rust-lang");
+    }
+
+    #[test]
+    fn test_facet_key_buckets_age_and_domain() {
+        let current_time = 1_000_000_000_000.0;
+        let one_hour_ago = current_time - 60.0 * 60.0 * 1000.0;
+        let entry = HistoryEntry::new("https://www.Example.com/page".to_string(), "Example".to_string(), 1, one_hour_ago);
+
+        assert_eq!(facet_key(&entry, Facet::Domain, current_time), "www.example.com");
+        assert_eq!(facet_key(&entry, Facet::Tld, current_time), "com");
+        assert_eq!(facet_key(&entry, Facet::AgeBucket, current_time), "today");
+    }
+
+    #[test]
+    fn test_facet_key_domain_buckets_count_entries_and_visits() {
+        let entries = vec![
+            HistoryEntry::new("https://github.com/a".to_string(), "A".to_string(), 2, 1000.0),
+            HistoryEntry::new("https://github.com/b".to_string(), "B".to_string(), 3, 1000.0),
+            HistoryEntry::new("https://other.com".to_string(), "C".to_string(), 1, 1000.0),
+        ];
+
+        let mut bucket_stats: HashMap<String, (u32, u32)> = HashMap::new();
+        for entry in &entries {
+            let key = facet_key(entry, Facet::Domain, 1000.0);
+            let stats = bucket_stats.entry(key).or_insert((0, 0));
+            stats.0 += 1;
+            stats.1 += entry.visit_count;
+        }
+
+        assert_eq!(bucket_stats.get("github.com"), Some(&(2, 5)));
+        assert_eq!(bucket_stats.get("other.com"), Some(&(1, 1)));
+    }
 }
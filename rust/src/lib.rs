@@ -1,6 +1,8 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
 
 // Data structures for browser history
 
@@ -70,24 +72,132 @@ const STOP_WORDS: &[&str] = &[
     "this", "that", "these", "those", "what", "when", "where", "who", "which", "how",
 ];
 
-// Extract keywords from a query (remove stop words, split on whitespace)
+// Minimum token length kept for Latin-script words; scripts without word
+// spacing (e.g. CJK) keep single-character tokens since one character can
+// carry meaning there.
+const MIN_LATIN_TOKEN_LEN: usize = 3;
+
+// True if every character in `token` belongs to a Latin Unicode block. Used
+// to decide whether the length floor and light stemming apply.
+fn is_latin_script(token: &str) -> bool {
+    token.chars().all(|c| {
+        c.is_ascii_alphanumeric() || matches!(c, '\u{00C0}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}')
+    })
+}
+
+// Diacritic-folds `text` (NFKD decomposition, then drops the combining
+// marks Unicode splits accents into), so "café" and "cafe" normalize to the
+// same text. Does not change case; callers lowercase first.
+fn fold_diacritics(text: &str) -> String {
+    text.nfkd().filter(|c| !('\u{0300}'..='\u{036F}').contains(c)).collect()
+}
+
+// Applies the same lowercase + diacritic-folding + light-stemming
+// normalization to entry title/url text that query tokens go through (see
+// `normalize_tokens`), so a stemmed query term like "study" matches a
+// document word like "studies" even when stemming doesn't just strip a
+// prefix-preserving suffix. Tokens are rejoined with spaces so callers can
+// keep doing substring (`.contains`) checks against the result.
+fn normalize_field(text: &str) -> String {
+    tokenize_words(&fold_diacritics(&text.to_lowercase()))
+        .into_iter()
+        .map(|word| if is_latin_script(&word) { light_stem(&word) } else { word })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Applies the same lowercase + diacritic-fold + light-stem normalization as
+// `normalize_field` to a single already-split keyword, so callers that pass
+// raw keywords straight from JS (bypassing `extract_keywords`/`parse_query`)
+// still line up with the stemmed document text `normalize_field` produces.
+// Unlike `normalize_tokens`, this never drops the keyword for being short:
+// the caller asked to match this exact word, so it stays even under
+// `MIN_LATIN_TOKEN_LEN`.
+fn normalize_keyword(keyword: &str) -> String {
+    let folded = fold_diacritics(&keyword.to_lowercase());
+    if is_latin_script(&folded) {
+        light_stem(&folded)
+    } else {
+        folded
+    }
+}
+
+// Naive light stemmer for Latin-script words: strips a handful of common
+// English suffixes (plurals, -ed, -ing, with doubled-consonant undoing) so
+// query/document variants like "running"/"run" or "books"/"book" land on
+// the same token. Not a full Porter stemmer, just enough to recover the
+// common cases; non-Latin tokens are left untouched.
+fn light_stem(word: &str) -> String {
+    let chars: Vec<char> = word.chars().collect();
+    let len = chars.len();
+
+    if len > 4 && word.ends_with("ied") {
+        let mut stem: String = chars[..len - 3].iter().collect();
+        stem.push('y');
+        return stem;
+    }
+    if len > 4 && word.ends_with("ing") {
+        let mut stem: String = chars[..len - 3].iter().collect();
+        if ends_with_doubled_consonant(&stem) {
+            stem.pop();
+        }
+        return stem;
+    }
+    if len > 4 && word.ends_with("ed") && !word.ends_with("eed") {
+        return chars[..len - 2].iter().collect();
+    }
+    if len > 3 && word.ends_with("ies") {
+        let mut stem: String = chars[..len - 3].iter().collect();
+        stem.push('y');
+        return stem;
+    }
+    if len > 3 && word.ends_with('s') && !word.ends_with("ss") {
+        return chars[..len - 1].iter().collect();
+    }
+
+    word.to_string()
+}
+
+fn ends_with_doubled_consonant(word: &str) -> bool {
+    let chars: Vec<char> = word.chars().collect();
+    let n = chars.len();
+    if n < 2 {
+        return false;
+    }
+    let (last, second_last) = (chars[n - 1], chars[n - 2]);
+    last == second_last && !matches!(last, 'l' | 's' | 'z' | 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+// Unicode-aware tokenization pipeline (inspired by charabia): segment on
+// Unicode word boundaries rather than ASCII whitespace, fold case and
+// diacritics, lightly stem Latin-script words, and drop tokens under
+// `MIN_LATIN_TOKEN_LEN` for scripts where that floor makes sense.
+fn normalize_tokens(text: &str) -> Vec<String> {
+    text.unicode_words()
+        .map(|word| fold_diacritics(&word.to_lowercase()))
+        .filter(|word| !word.is_empty())
+        .map(|word| if is_latin_script(&word) { light_stem(&word) } else { word })
+        .filter(|word| !is_latin_script(word) || word.chars().count() >= MIN_LATIN_TOKEN_LEN)
+        .collect()
+}
+
+// Extract keywords from a query: Unicode-normalized tokens with stop words
+// removed.
 #[wasm_bindgen]
 pub fn extract_keywords(text: &str) -> Vec<String> {
-    text.to_lowercase()
-        .split_whitespace()
-        .map(|word| {
-            // Remove punctuation from word
-            word.chars()
-                .filter(|c| c.is_alphanumeric())
-                .collect::<String>()
-        })
-        .filter(|word| {
-            // Filter out stop words and very short words
-            !word.is_empty() && word.len() > 2 && !STOP_WORDS.contains(&word.as_str())
-        })
+    normalize_tokens(text)
+        .into_iter()
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
         .collect()
 }
 
+// Exposes the normalized token stream (before stop-word filtering) so the
+// JS side can preview exactly how a piece of text will be tokenized.
+#[wasm_bindgen]
+pub fn preview_normalized_tokens(text: &str) -> Vec<String> {
+    normalize_tokens(text)
+}
+
 // Extract domain from URL
 fn extract_domain(url: &str) -> String {
     // Simple domain extraction
@@ -101,42 +211,449 @@ fn extract_domain(url: &str) -> String {
     url.to_string()
 }
 
-// Calculate relevance score for a history entry based on keywords
-fn calculate_relevance_score(entry: &HistoryEntry, keywords: &[String], current_time: f64) -> f64 {
+// Collapses a domain to its registrable form by keeping only the last two
+// dot-separated labels (e.g. "docs.rust-lang.org" -> "rust-lang.org"). This
+// is a simple heuristic, not a full public-suffix-list lookup.
+fn registrable_domain(domain: &str) -> String {
+    let labels: Vec<&str> = domain.split('.').collect();
+    if labels.len() <= 2 {
+        domain.to_string()
+    } else {
+        labels[labels.len() - 2..].join(".")
+    }
+}
+
+// Which part of an entry to treat as its "distinct" identity when
+// collapsing near-duplicate results, mirroring MeiliSearch's `distinct`
+// attribute.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistinctKey {
+    Domain,
+    RegistrableDomain,
+    FullUrl,
+}
+
+fn distinct_key(entry: &HistoryEntry, mode: DistinctKey) -> String {
+    match mode {
+        DistinctKey::Domain => extract_domain(&entry.url),
+        DistinctKey::RegistrableDomain => registrable_domain(&extract_domain(&entry.url)),
+        DistinctKey::FullUrl => entry.url.to_lowercase(),
+    }
+}
+
+// Walks `entries` in rank order, keeping the first entry seen for each
+// distinct key until `max_results` are collected. Returns the kept entries
+// alongside, per kept key, how many later duplicates were skipped before
+// the walk stopped (so a UI can show "+N more from this site").
+fn dedupe_by_distinct(
+    entries: Vec<HistoryEntry>,
+    mode: DistinctKey,
+    max_results: usize,
+) -> (Vec<HistoryEntry>, HashMap<String, u32>) {
+    let mut seen = std::collections::HashSet::new();
+    let mut suppressed: HashMap<String, u32> = HashMap::new();
+    let mut kept = Vec::new();
+
+    for entry in entries {
+        if kept.len() >= max_results {
+            break;
+        }
+
+        let key = distinct_key(&entry, mode);
+        if seen.insert(key.clone()) {
+            kept.push(entry);
+        } else {
+            *suppressed.entry(key).or_insert(0) += 1;
+        }
+    }
+
+    (kept, suppressed)
+}
+
+// Configuration for fuzzy (typo-tolerant) keyword matching. Longer keywords
+// are allowed proportionally more typos, mirroring MeiliSearch's typo rules.
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzyConfig {
+    pub max_typos_short: u32,
+    pub max_typos_medium: u32,
+    pub max_typos_long: u32,
+    pub medium_len_threshold: u32,
+    pub long_len_threshold: u32,
+}
+
+#[wasm_bindgen]
+impl FuzzyConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> FuzzyConfig {
+        FuzzyConfig::default()
+    }
+}
+
+impl Default for FuzzyConfig {
+    fn default() -> Self {
+        FuzzyConfig {
+            max_typos_short: 0,
+            max_typos_medium: 1,
+            max_typos_long: 2,
+            medium_len_threshold: 5,
+            long_len_threshold: 9,
+        }
+    }
+}
+
+impl FuzzyConfig {
+    fn max_typos_for(&self, keyword_len: usize) -> u32 {
+        if keyword_len as u32 >= self.long_len_threshold {
+            self.max_typos_long
+        } else if keyword_len as u32 >= self.medium_len_threshold {
+            self.max_typos_medium
+        } else {
+            self.max_typos_short
+        }
+    }
+}
+
+// Split a lowercased field into word tokens, the same unit fuzzy matching
+// judges typos against (so "pyton" can match the token "python").
+fn tokenize_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_string())
+        .collect()
+}
+
+// Bounded Levenshtein edit distance over a two-row DP table. Bails out as
+// soon as the running minimum of a row exceeds `max_distance`, so words that
+// are clearly too far apart are rejected in O(len) instead of O(n*m).
+fn bounded_levenshtein(a: &str, b: &str, max_distance: u32) -> Option<u32> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let max_distance = max_distance as usize;
+
+    if a.len().abs_diff(b.len()) > max_distance {
+        return None;
+    }
+
+    let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+    let mut curr_row = vec![0usize; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr_row[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            curr_row[j] = (prev_row[j] + 1)
+                .min(curr_row[j - 1] + 1)
+                .min(prev_row[j - 1] + cost);
+        }
+
+        if *curr_row.iter().min().unwrap() > max_distance {
+            return None;
+        }
+
+        std::mem::swap(&mut prev_row, &mut curr_row);
+    }
+
+    let distance = prev_row[b.len()];
+    (distance <= max_distance).then_some(distance as u32)
+}
+
+// Finds the fewest typos needed for `keyword` to match a word in `field_lower`.
+// An exact substring hit always wins with 0 typos; otherwise each token is
+// tried against the bounded Levenshtein budget for the keyword's length.
+fn fuzzy_match_typos(keyword: &str, field_lower: &str, config: &FuzzyConfig) -> Option<u32> {
+    if field_lower.contains(keyword) {
+        return Some(0);
+    }
+
+    let max_typos = config.max_typos_for(keyword.len());
+    if max_typos == 0 {
+        return None;
+    }
+
+    tokenize_words(field_lower)
+        .iter()
+        .filter_map(|token| bounded_levenshtein(keyword, token, max_typos))
+        .min()
+}
+
+// Maximum total proximity bonus per field, so a long run of adjacent
+// keywords can't accumulate an unbounded bonus.
+const MAX_PROXIMITY_BONUS: f64 = 20.0;
+
+// Finds the smallest absolute word-position gap between any occurrence of
+// `a` and any occurrence of `b` in `field_tokens`. Returns `None` if either
+// keyword doesn't appear at all.
+fn min_position_gap(a: &str, b: &str, field_tokens: &[String]) -> Option<usize> {
+    let positions_a = field_tokens.iter().enumerate().filter(|(_, t)| t.as_str() == a);
+    let positions_b: Vec<usize> = field_tokens
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.as_str() == b)
+        .map(|(i, _)| i)
+        .collect();
+
+    positions_a
+        .flat_map(|(pa, _)| positions_b.iter().map(move |&pb| pa.abs_diff(pb)))
+        .min()
+}
+
+// Rewards entries where adjacent query keywords appear near each other in
+// `field_lower`, decaying with the gap (`8 - gap`, floored at 0) and summed
+// over each adjacent keyword pair, so "rust async" ranks a page with those
+// words side by side over one where they're paragraphs apart.
+fn proximity_bonus(keywords: &[String], field_lower: &str) -> f64 {
+    if keywords.len() < 2 {
+        return 0.0;
+    }
+
+    let field_tokens = tokenize_words(field_lower);
+    let bonus: f64 = keywords
+        .windows(2)
+        .filter_map(|pair| min_position_gap(&pair[0], &pair[1], &field_tokens))
+        .map(|gap| (8.0 - gap as f64).max(0.0))
+        .sum();
+
+    bonus.min(MAX_PROXIMITY_BONUS)
+}
+
+// True if `phrase` appears as a contiguous run inside `field_tokens`,
+// i.e. a quoted query span matched word-for-word and in order.
+fn phrase_matches(phrase: &[String], field_tokens: &[String]) -> bool {
+    if phrase.is_empty() || phrase.len() > field_tokens.len() {
+        return false;
+    }
+    field_tokens.windows(phrase.len()).any(|window| window == phrase)
+}
+
+// Calculate relevance score for a history entry based on keywords and
+// quoted phrases. When `fuzzy_config` is `Some`, keywords that don't match
+// exactly may still score via a reduced bonus that shrinks as the typo
+// count grows, so exact hits always outrank fuzzy ones.
+fn calculate_relevance_score(
+    entry: &HistoryEntry,
+    keywords: &[String],
+    phrases: &[Vec<String>],
+    current_time: f64,
+    fuzzy_config: Option<&FuzzyConfig>,
+) -> f64 {
     let mut score = 0.0;
 
-    let url_lower = entry.url.to_lowercase();
-    let title_lower = entry.title.to_lowercase();
+    let url_lower = normalize_field(&entry.url);
+    let title_lower = normalize_field(&entry.title);
 
     // Keyword matching scores
     for keyword in keywords {
-        // Title matches are worth more
-        if title_lower.contains(keyword) {
-            score += 3.0;
+        match fuzzy_config {
+            Some(config) => {
+                // Title matches are worth more
+                if let Some(typos) = fuzzy_match_typos(keyword, &title_lower, config) {
+                    score += if typos == 0 { 3.0 } else { 3.0 / (typos as f64 + 2.0) };
+                }
+                // URL matches
+                if let Some(typos) = fuzzy_match_typos(keyword, &url_lower, config) {
+                    score += if typos == 0 { 2.0 } else { 2.0 / (typos as f64 + 2.0) };
+                }
+            }
+            None => {
+                // Title matches are worth more
+                if title_lower.contains(keyword) {
+                    score += 3.0;
+                }
+                // URL matches
+                if url_lower.contains(keyword) {
+                    score += 2.0;
+                }
+            }
         }
-        // URL matches
-        if url_lower.contains(keyword) {
-            score += 2.0;
+    }
+
+    // Proximity: reward keywords that land near each other, title weighted
+    // higher than url like the exact/fuzzy match bonuses above.
+    score += proximity_bonus(keywords, &title_lower);
+    score += proximity_bonus(keywords, &url_lower) * 0.5;
+
+    // Phrase matches: a pinned multi-word phrase is a much stronger signal
+    // than any individual keyword, title again weighted over url.
+    let title_tokens = tokenize_words(&title_lower);
+    let url_tokens = tokenize_words(&url_lower);
+    for phrase in phrases {
+        if phrase_matches(phrase, &title_tokens) {
+            score += 10.0;
+        } else if phrase_matches(phrase, &url_tokens) {
+            score += 6.0;
         }
     }
 
-    // Visit count bonus (logarithmic scale to avoid over-weighting)
-    score += (entry.visit_count as f64).ln() * 0.5;
+    score += visit_and_recency_bonus(entry, current_time);
+
+    score
+}
+
+// Visit count and recency bonus shared by every scoring mode (keyword,
+// fuzzy, BM25): a logarithmic visit-count bonus plus a recency bucket bonus
+// that rewards entries visited more recently.
+fn visit_and_recency_bonus(entry: &HistoryEntry, current_time: f64) -> f64 {
+    let mut bonus = (entry.visit_count as f64).ln() * 0.5;
 
-    // Recency bonus (more recent = higher score)
     let time_diff = current_time - entry.last_visit_time;
     let days_old = time_diff / (1000.0 * 60.0 * 60.0 * 24.0);
 
-    // Decay factor: recent visits get more weight
     if days_old < 1.0 {
-        score += 2.0; // Visited today
+        bonus += 2.0; // Visited today
     } else if days_old < 7.0 {
-        score += 1.0; // Visited this week
+        bonus += 1.0; // Visited this week
     } else if days_old < 30.0 {
-        score += 0.5; // Visited this month
+        bonus += 0.5; // Visited this month
     }
 
-    score
+    bonus
+}
+
+// Corpus-wide term statistics needed for BM25: how many documents each term
+// appears in, and the average document length (title+url tokens).
+struct Bm25Stats {
+    doc_freq: HashMap<String, u32>,
+    avg_doc_len: f64,
+    num_docs: u32,
+}
+
+impl Bm25Stats {
+    fn build(entries: &[HistoryEntry]) -> Bm25Stats {
+        let mut doc_freq: HashMap<String, u32> = HashMap::new();
+        let mut total_len: u64 = 0;
+
+        for entry in entries {
+            let tokens = document_tokens(entry);
+            total_len += tokens.len() as u64;
+
+            let unique_terms: std::collections::HashSet<&String> = tokens.iter().collect();
+            for term in unique_terms {
+                *doc_freq.entry(term.clone()).or_insert(0) += 1;
+            }
+        }
+
+        let num_docs = entries.len() as u32;
+        let avg_doc_len = if num_docs == 0 {
+            0.0
+        } else {
+            total_len as f64 / num_docs as f64
+        };
+
+        Bm25Stats {
+            doc_freq,
+            avg_doc_len,
+            num_docs,
+        }
+    }
+
+    fn idf(&self, term: &str) -> f64 {
+        let df = *self.doc_freq.get(term).unwrap_or(&0) as f64;
+        ((self.num_docs as f64 - df + 0.5) / (df + 0.5) + 1.0).ln()
+    }
+}
+
+// A BM25 "document" is an entry's title and url tokenized together.
+fn document_tokens(entry: &HistoryEntry) -> Vec<String> {
+    let mut tokens = tokenize_words(&normalize_field(&entry.title));
+    tokens.extend(tokenize_words(&normalize_field(&entry.url)));
+    tokens
+}
+
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+// Okapi BM25 relevance score for `entry` against `keywords`, using
+// corpus-wide term statistics so a rare keyword outweighs a common one.
+// Blended with the same visit-count/recency bonus as keyword-based scoring,
+// so popularity and freshness still matter once term rarity is accounted for.
+fn calculate_bm25_score(
+    entry: &HistoryEntry,
+    keywords: &[String],
+    current_time: f64,
+    stats: &Bm25Stats,
+) -> f64 {
+    let tokens = document_tokens(entry);
+    let dl = tokens.len() as f64;
+
+    let mut score = 0.0;
+    for keyword in keywords {
+        let tf = tokens.iter().filter(|t| t.as_str() == keyword).count() as f64;
+        if tf == 0.0 {
+            continue;
+        }
+
+        let idf = stats.idf(keyword);
+        let denom = tf + BM25_K1 * (1.0 - BM25_B + BM25_B * dl / stats.avg_doc_len.max(1.0));
+        score += idf * (tf * (BM25_K1 + 1.0)) / denom;
+    }
+
+    score + visit_and_recency_bonus(entry, current_time)
+}
+
+// A parsed search query: quoted spans become ordered phrases that must
+// match contiguously, everything else is tokenized into regular keywords.
+struct ParsedQuery {
+    phrases: Vec<Vec<String>>,
+    keywords: Vec<String>,
+}
+
+// Splits `query` into quoted phrases and ordinary keywords. Quoted spans are
+// tokenized the same way entry fields are, so phrase matching compares like
+// with like. Returns an error if the quotes don't balance.
+fn parse_query(query: &str) -> Result<ParsedQuery, String> {
+    if !query.matches('"').count().is_multiple_of(2) {
+        return Err("Unbalanced quotes in query".to_string());
+    }
+
+    let mut phrases = Vec::new();
+    let mut rest = String::new();
+    let mut in_quotes = false;
+    let mut current = String::new();
+
+    for c in query.chars() {
+        if c == '"' {
+            if in_quotes {
+                let tokens = tokenize_words(&normalize_field(&current));
+                if !tokens.is_empty() {
+                    phrases.push(tokens);
+                }
+                current.clear();
+            }
+            in_quotes = !in_quotes;
+            continue;
+        }
+        if in_quotes {
+            current.push(c);
+        } else {
+            rest.push(c);
+        }
+    }
+
+    let keywords = extract_keywords(&rest);
+    Ok(ParsedQuery { phrases, keywords })
+}
+
+// True if `entry` satisfies every quoted phrase (if any) and matches at
+// least one plain keyword (if any are present).
+fn entry_matches_query(entry: &HistoryEntry, parsed: &ParsedQuery) -> bool {
+    let url_lower = normalize_field(&entry.url);
+    let title_lower = normalize_field(&entry.title);
+
+    let phrases_ok = parsed.phrases.iter().all(|phrase| {
+        phrase_matches(phrase, &tokenize_words(&title_lower))
+            || phrase_matches(phrase, &tokenize_words(&url_lower))
+    });
+    if !phrases_ok {
+        return false;
+    }
+
+    parsed.keywords.is_empty()
+        || parsed
+            .keywords
+            .iter()
+            .any(|keyword| url_lower.contains(keyword) || title_lower.contains(keyword))
 }
 
 // Basic filtering functions
@@ -167,13 +684,13 @@ pub fn filter_history_by_keywords(
     let entries: Vec<HistoryEntry> = serde_wasm_bindgen::from_value(entries)
         .map_err(|e| JsValue::from_str(&format!("Failed to parse entries: {}", e)))?;
 
-    let keywords_lower: Vec<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
+    let keywords_lower: Vec<String> = keywords.iter().map(|k| normalize_keyword(k)).collect();
 
     let filtered: Vec<HistoryEntry> = entries
         .into_iter()
         .filter(|entry| {
-            let url_lower = entry.url.to_lowercase();
-            let title_lower = entry.title.to_lowercase();
+            let url_lower = normalize_field(&entry.url);
+            let title_lower = normalize_field(&entry.title);
             keywords_lower.iter().any(|keyword| {
                 url_lower.contains(keyword) || title_lower.contains(keyword)
             })
@@ -200,26 +717,41 @@ pub fn sort_history_by_relevance(entries: JsValue) -> Result<JsValue, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
 
-// Advanced: Sort by relevance with keyword matching
+// Advanced: Sort by relevance with keyword matching. Pass `use_bm25 = Some(true)`
+// to rank by Okapi BM25 (corpus-wide term rarity) instead of the default
+// additive keyword/visit/recency score; omitted or `None` keeps the
+// original behavior.
 #[wasm_bindgen]
 pub fn sort_by_relevance_with_keywords(
     entries: JsValue,
     keywords: Vec<String>,
     current_time: f64,
+    use_bm25: Option<bool>,
 ) -> Result<JsValue, JsValue> {
     let entries: Vec<HistoryEntry> = serde_wasm_bindgen::from_value(entries)
         .map_err(|e| JsValue::from_str(&format!("Failed to parse entries: {}", e)))?;
 
-    let keywords_lower: Vec<String> = keywords.iter().map(|k| k.to_lowercase()).collect();
+    let keywords_lower: Vec<String> = keywords.iter().map(|k| normalize_keyword(k)).collect();
 
     // Calculate scores for all entries
-    let mut scored_entries: Vec<(HistoryEntry, f64)> = entries
-        .into_iter()
-        .map(|entry| {
-            let score = calculate_relevance_score(&entry, &keywords_lower, current_time);
-            (entry, score)
-        })
-        .collect();
+    let mut scored_entries: Vec<(HistoryEntry, f64)> = if use_bm25.unwrap_or(false) {
+        let stats = Bm25Stats::build(&entries);
+        entries
+            .into_iter()
+            .map(|entry| {
+                let score = calculate_bm25_score(&entry, &keywords_lower, current_time, &stats);
+                (entry, score)
+            })
+            .collect()
+    } else {
+        entries
+            .into_iter()
+            .map(|entry| {
+                let score = calculate_relevance_score(&entry, &keywords_lower, &[], current_time, None);
+                (entry, score)
+            })
+            .collect()
+    };
 
     // Sort by score (descending)
     scored_entries.sort_by(|a, b| {
@@ -236,6 +768,37 @@ pub fn sort_by_relevance_with_keywords(
         .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
 
+// Fuzzy counterpart of `filter_history_by_keywords` (see also
+// `find_relevant_history_fuzzy`): same shape, but typo-tolerant via
+// `FuzzyConfig` (defaults used when `config` is `None`).
+#[wasm_bindgen]
+pub fn filter_history_by_keywords_fuzzy(
+    entries: JsValue,
+    keywords: Vec<String>,
+    config: Option<FuzzyConfig>,
+) -> Result<JsValue, JsValue> {
+    let entries: Vec<HistoryEntry> = serde_wasm_bindgen::from_value(entries)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse entries: {}", e)))?;
+
+    let config = config.unwrap_or_default();
+    let keywords_lower: Vec<String> = keywords.iter().map(|k| normalize_keyword(k)).collect();
+
+    let filtered: Vec<HistoryEntry> = entries
+        .into_iter()
+        .filter(|entry| {
+            let url_lower = normalize_field(&entry.url);
+            let title_lower = normalize_field(&entry.title);
+            keywords_lower.iter().any(|keyword| {
+                fuzzy_match_typos(keyword, &title_lower, &config).is_some()
+                    || fuzzy_match_typos(keyword, &url_lower, &config).is_some()
+            })
+        })
+        .collect();
+
+    serde_wasm_bindgen::to_value(&filtered)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
 #[wasm_bindgen]
 pub fn limit_history_results(entries: JsValue, max_count: usize) -> Result<JsValue, JsValue> {
     let entries: Vec<HistoryEntry> = serde_wasm_bindgen::from_value(entries)
@@ -327,7 +890,34 @@ pub fn analyze_domain_patterns(entries: JsValue) -> Result<JsValue, JsValue> {
         .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
 
-// Combined query function: filter, score, and sort
+// Filters `entries` down to ones satisfying `parsed`, then scores
+// (phrase- and proximity-aware) and sorts them best-first. Unlike
+// `find_relevant_history`, this does not truncate to `max_results`, so
+// callers that need to post-process the full ranked list (e.g. dedupe) can.
+fn filter_and_rank_by_query(entries: Vec<HistoryEntry>, parsed: &ParsedQuery, current_time: f64) -> Vec<HistoryEntry> {
+    let filtered: Vec<HistoryEntry> = entries
+        .into_iter()
+        .filter(|entry| entry_matches_query(entry, parsed))
+        .collect();
+
+    let mut scored_entries: Vec<(HistoryEntry, f64)> = filtered
+        .into_iter()
+        .map(|entry| {
+            let score = calculate_relevance_score(&entry, &parsed.keywords, &parsed.phrases, current_time, None);
+            (entry, score)
+        })
+        .collect();
+
+    scored_entries.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    scored_entries.into_iter().map(|(entry, _)| entry).collect()
+}
+
+// Combined query function: filter, score, and sort. Recognizes
+// double-quoted spans in `query` as ordered phrases that must match
+// contiguously; returns an error if the quotes in `query` are unbalanced.
 #[wasm_bindgen]
 pub fn find_relevant_history(
     entries: JsValue,
@@ -338,33 +928,491 @@ pub fn find_relevant_history(
     let entries: Vec<HistoryEntry> = serde_wasm_bindgen::from_value(entries)
         .map_err(|e| JsValue::from_str(&format!("Failed to parse entries: {}", e)))?;
 
-    // Extract keywords from query
+    // Extract phrases and keywords from query
+    let parsed = parse_query(query).map_err(|e| JsValue::from_str(&e))?;
+
+    if parsed.keywords.is_empty() && parsed.phrases.is_empty() {
+        // No keywords, just sort by recency and visit count
+        let sorted_entries = serde_wasm_bindgen::to_value(&entries)?;
+        return sort_history_by_relevance(sorted_entries);
+    }
+
+    let sorted: Vec<HistoryEntry> = filter_and_rank_by_query(entries, &parsed, current_time)
+        .into_iter()
+        .take(max_results)
+        .collect();
+
+    serde_wasm_bindgen::to_value(&sorted)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
+// Distinct counterpart of `find_relevant_history`: ranks the same way, but
+// collapses near-duplicate results so one noisy domain can't fill the whole
+// result set (MeiliSearch's `distinct` attribute). `distinct_key` selects
+// whether "duplicate" means same domain, same registrable domain, or same
+// full URL.
+#[wasm_bindgen]
+pub fn find_relevant_history_distinct(
+    entries: JsValue,
+    query: &str,
+    max_results: usize,
+    current_time: f64,
+    distinct_key: DistinctKey,
+) -> Result<JsValue, JsValue> {
+    let entries: Vec<HistoryEntry> = serde_wasm_bindgen::from_value(entries)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse entries: {}", e)))?;
+
+    let parsed = parse_query(query).map_err(|e| JsValue::from_str(&e))?;
+
+    let ranked = if parsed.keywords.is_empty() && parsed.phrases.is_empty() {
+        let mut ranked = entries;
+        ranked.sort_by(|a, b| {
+            b.visit_count
+                .cmp(&a.visit_count)
+                .then_with(|| b.last_visit_time.partial_cmp(&a.last_visit_time).unwrap_or(std::cmp::Ordering::Equal))
+        });
+        ranked
+    } else {
+        filter_and_rank_by_query(entries, &parsed, current_time)
+    };
+
+    let (deduped, _suppressed) = dedupe_by_distinct(ranked, distinct_key, max_results);
+
+    serde_wasm_bindgen::to_value(&deduped)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
+// Companion to `find_relevant_history_distinct`: returns both the deduped
+// results and, per kept domain/key, how many duplicate entries were
+// suppressed in its favor, so a UI can render "+N more from this site".
+#[wasm_bindgen]
+pub fn find_relevant_history_distinct_with_counts(
+    entries: JsValue,
+    query: &str,
+    max_results: usize,
+    current_time: f64,
+    distinct_key: DistinctKey,
+) -> Result<JsValue, JsValue> {
+    let entries: Vec<HistoryEntry> = serde_wasm_bindgen::from_value(entries)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse entries: {}", e)))?;
+
+    let parsed = parse_query(query).map_err(|e| JsValue::from_str(&e))?;
+
+    let ranked = if parsed.keywords.is_empty() && parsed.phrases.is_empty() {
+        let mut ranked = entries;
+        ranked.sort_by(|a, b| {
+            b.visit_count
+                .cmp(&a.visit_count)
+                .then_with(|| b.last_visit_time.partial_cmp(&a.last_visit_time).unwrap_or(std::cmp::Ordering::Equal))
+        });
+        ranked
+    } else {
+        filter_and_rank_by_query(entries, &parsed, current_time)
+    };
+
+    let (deduped, suppressed) = dedupe_by_distinct(ranked, distinct_key, max_results);
+
+    serde_wasm_bindgen::to_value(&serde_json::json!({
+        "results": deduped,
+        "suppressed_counts": suppressed,
+    }))
+    .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
+// Typo-tolerant counterpart of `find_relevant_history`, using `FuzzyConfig`
+// to decide how many typos a keyword may have (defaults used when `config`
+// is `None`).
+#[wasm_bindgen]
+pub fn find_relevant_history_fuzzy(
+    entries: JsValue,
+    query: &str,
+    max_results: usize,
+    current_time: f64,
+    config: Option<FuzzyConfig>,
+) -> Result<JsValue, JsValue> {
+    let entries: Vec<HistoryEntry> = serde_wasm_bindgen::from_value(entries)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse entries: {}", e)))?;
+
     let keywords = extract_keywords(query);
 
     if keywords.is_empty() {
-        // No keywords, just sort by recency and visit count
         let sorted_entries = serde_wasm_bindgen::to_value(&entries)?;
         return sort_history_by_relevance(sorted_entries);
     }
 
-    // Filter entries that match keywords
+    let config = config.unwrap_or_default();
+
     let filtered: Vec<HistoryEntry> = entries
         .into_iter()
         .filter(|entry| {
-            let url_lower = entry.url.to_lowercase();
-            let title_lower = entry.title.to_lowercase();
+            let url_lower = normalize_field(&entry.url);
+            let title_lower = normalize_field(&entry.title);
             keywords.iter().any(|keyword| {
-                url_lower.contains(keyword) || title_lower.contains(keyword)
+                fuzzy_match_typos(keyword, &title_lower, &config).is_some()
+                    || fuzzy_match_typos(keyword, &url_lower, &config).is_some()
             })
         })
         .collect();
 
-    // Score and sort
-    let scored_entries = serde_wasm_bindgen::to_value(&filtered)?;
-    let sorted = sort_by_relevance_with_keywords(scored_entries, keywords, current_time)?;
+    let mut scored_entries: Vec<(HistoryEntry, f64)> = filtered
+        .into_iter()
+        .map(|entry| {
+            let score = calculate_relevance_score(&entry, &keywords, &[], current_time, Some(&config));
+            (entry, score)
+        })
+        .collect();
+
+    scored_entries.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let sorted: Vec<HistoryEntry> = scored_entries
+        .into_iter()
+        .map(|(entry, _)| entry)
+        .take(max_results)
+        .collect();
+
+    serde_wasm_bindgen::to_value(&sorted)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
+// How aggressively `find_relevant_history_graded` relaxes "must match every
+// keyword" once that's too strict to fill `max_results`, mirroring
+// MeiliSearch's termsMatchingStrategy setting. Quoted phrases are never
+// relaxed under any strategy.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermsMatchingStrategy {
+    All,
+    Last,
+    Frequency,
+}
+
+// Which keyword indices to give up on, and in what order, once requiring all
+// of them returns too few results. `All` never gives up. `Last` drops from
+// the end of the query first. `Frequency` drops the term with the highest
+// corpus document frequency first, since it's the least discriminating.
+fn terms_drop_order(
+    keywords: &[String],
+    strategy: TermsMatchingStrategy,
+    entries: &[HistoryEntry],
+) -> Vec<usize> {
+    match strategy {
+        TermsMatchingStrategy::All => Vec::new(),
+        TermsMatchingStrategy::Last => (0..keywords.len()).rev().collect(),
+        TermsMatchingStrategy::Frequency => {
+            let stats = Bm25Stats::build(entries);
+            let mut order: Vec<usize> = (0..keywords.len()).collect();
+            order.sort_by(|&a, &b| {
+                let df_a = stats.doc_freq.get(&keywords[a]).copied().unwrap_or(0);
+                let df_b = stats.doc_freq.get(&keywords[b]).copied().unwrap_or(0);
+                df_b.cmp(&df_a)
+            });
+            order
+        }
+    }
+}
+
+// `entry`, tagged with how many of the query's plain keywords it actually
+// matched, so a caller can tell a full match from one returned only because
+// `find_relevant_history_graded` had to relax the requirement.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GradedMatch {
+    entry: HistoryEntry,
+    matched_terms: usize,
+}
+
+// Core logic behind `find_relevant_history_graded`, kept free of
+// `wasm_bindgen`/`JsValue` so it can be exercised directly in tests.
+// `None` means the query had neither keywords nor phrases, so the caller
+// should fall back to plain relevance sorting instead.
+fn graded_matches_internal(
+    entries: Vec<HistoryEntry>,
+    parsed: &ParsedQuery,
+    max_results: usize,
+    current_time: f64,
+    strategy: TermsMatchingStrategy,
+) -> Option<Vec<GradedMatch>> {
+    if parsed.keywords.is_empty() && parsed.phrases.is_empty() {
+        return None;
+    }
+
+    let order = terms_drop_order(&parsed.keywords, strategy, &entries);
+
+    // Phrase requirement and per-keyword hit mask, computed once up front:
+    // which entries are eligible at all, and which of the query's keywords
+    // each eligible entry actually contains.
+    let candidates: Vec<(HistoryEntry, Vec<bool>)> = entries
+        .into_iter()
+        .filter_map(|entry| {
+            let url_lower = normalize_field(&entry.url);
+            let title_lower = normalize_field(&entry.title);
+            let title_tokens = tokenize_words(&title_lower);
+            let url_tokens = tokenize_words(&url_lower);
+
+            let phrases_ok = parsed.phrases.iter().all(|phrase| {
+                phrase_matches(phrase, &title_tokens) || phrase_matches(phrase, &url_tokens)
+            });
+            if !phrases_ok {
+                return None;
+            }
+
+            let hits: Vec<bool> = parsed
+                .keywords
+                .iter()
+                .map(|keyword| url_lower.contains(keyword.as_str()) || title_lower.contains(keyword.as_str()))
+                .collect();
+            Some((entry, hits))
+        })
+        .collect();
+
+    let mut kept = vec![false; candidates.len()];
+    let mut results: Vec<GradedMatch> = Vec::new();
+    let mut dropped = 0;
+
+    loop {
+        let required: Vec<usize> = (0..parsed.keywords.len())
+            .filter(|i| !order[..dropped].contains(i))
+            .collect();
+
+        let mut level: Vec<(usize, &HistoryEntry, usize)> = candidates
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !kept[*i])
+            .filter_map(|(i, (entry, hits))| {
+                if required.iter().all(|&r| hits[r]) {
+                    let matched_terms = hits.iter().filter(|hit| **hit).count();
+                    Some((i, entry, matched_terms))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        level.sort_by(|a, b| {
+            let score_a = calculate_relevance_score(a.1, &parsed.keywords, &parsed.phrases, current_time, None);
+            let score_b = calculate_relevance_score(b.1, &parsed.keywords, &parsed.phrases, current_time, None);
+            score_b.partial_cmp(&score_a).unwrap_or(std::cmp::Ordering::Equal)
+        });
 
-    // Limit results
-    limit_history_results(sorted, max_results)
+        for (i, entry, matched_terms) in level {
+            kept[i] = true;
+            results.push(GradedMatch {
+                entry: entry.clone(),
+                matched_terms,
+            });
+        }
+
+        if results.len() >= max_results || dropped >= order.len() {
+            break;
+        }
+        dropped += 1;
+    }
+
+    results.truncate(max_results);
+
+    Some(results)
+}
+
+// Combined query + graceful-degradation function: like `find_relevant_history`,
+// but if requiring every keyword (phrases are always required) leaves fewer
+// than `max_results` entries, it progressively drops keywords per `strategy`
+// and re-queries, appending the looser matches after the fuller ones until
+// there are enough results or no keywords are left to drop.
+#[wasm_bindgen]
+pub fn find_relevant_history_graded(
+    entries: JsValue,
+    query: &str,
+    max_results: usize,
+    current_time: f64,
+    strategy: TermsMatchingStrategy,
+) -> Result<JsValue, JsValue> {
+    let entries: Vec<HistoryEntry> = serde_wasm_bindgen::from_value(entries)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse entries: {}", e)))?;
+
+    let parsed = parse_query(query).map_err(|e| JsValue::from_str(&e))?;
+
+    let results = match graded_matches_internal(entries.clone(), &parsed, max_results, current_time, strategy) {
+        Some(results) => results,
+        None => {
+            let sorted_entries = serde_wasm_bindgen::to_value(&entries)?;
+            return sort_history_by_relevance(sorted_entries);
+        }
+    };
+
+    serde_wasm_bindgen::to_value(&results)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+}
+
+// One criterion in an ordered ranking pipeline, modeled on MeiliSearch's
+// ranking rules. `rank_history` applies these as successive tie-breakers
+// instead of flattening everything into a single score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingRule {
+    Words,
+    Typo,
+    Proximity,
+    VisitCount,
+    Recency,
+    Exactness,
+}
+
+// Default pipeline: prefer entries matching more keywords, then exact over
+// fuzzy matches, then popularity, then recency. This does not reproduce the
+// blended `calculate_relevance_score` ordering: a keyword counts once whether
+// it hits the title or the url, and visit count and recency only break ties.
+const DEFAULT_RANKING_RULES: [RankingRule; 4] = [
+    RankingRule::Words,
+    RankingRule::Exactness,
+    RankingRule::VisitCount,
+    RankingRule::Recency,
+];
+
+// Per-entry values each `RankingRule` compares on. `proximity` is the best
+// adjacent-keyword proximity bonus across title/url (see `proximity_bonus`).
+#[derive(Debug, Clone, Copy)]
+struct RuleMetrics {
+    words_matched: u32,
+    typo_total: u32,
+    proximity: u32,
+    visit_count: u32,
+    recency_days: f64,
+    exactness: u32,
+}
+
+fn compute_rule_metrics(entry: &HistoryEntry, keywords: &[String], current_time: f64) -> RuleMetrics {
+    let url_lower = normalize_field(&entry.url);
+    let title_lower = normalize_field(&entry.title);
+    let config = FuzzyConfig::default();
+
+    let mut words_matched = 0u32;
+    let mut typo_total = 0u32;
+    let mut exactness = 0u32;
+
+    for keyword in keywords {
+        let best_typos = [
+            fuzzy_match_typos(keyword, &title_lower, &config),
+            fuzzy_match_typos(keyword, &url_lower, &config),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+
+        if let Some(typos) = best_typos {
+            words_matched += 1;
+            typo_total += typos;
+            if typos == 0 {
+                exactness += 1;
+            }
+        }
+    }
+
+    let days_old = (current_time - entry.last_visit_time) / (1000.0 * 60.0 * 60.0 * 24.0);
+
+    let proximity = proximity_bonus(keywords, &title_lower).max(proximity_bonus(keywords, &url_lower));
+
+    RuleMetrics {
+        words_matched,
+        typo_total,
+        proximity: proximity.round() as u32,
+        visit_count: entry.visit_count,
+        recency_days: days_old,
+        exactness,
+    }
+}
+
+fn compare_rule(rule: RankingRule, a: &RuleMetrics, b: &RuleMetrics) -> std::cmp::Ordering {
+    match rule {
+        RankingRule::Words => b.words_matched.cmp(&a.words_matched),
+        RankingRule::Typo => a.typo_total.cmp(&b.typo_total),
+        // Higher proximity bonus means keywords landed closer together.
+        RankingRule::Proximity => b.proximity.cmp(&a.proximity),
+        RankingRule::VisitCount => b.visit_count.cmp(&a.visit_count),
+        RankingRule::Recency => a
+            .recency_days
+            .partial_cmp(&b.recency_days)
+            .unwrap_or(std::cmp::Ordering::Equal),
+        RankingRule::Exactness => b.exactness.cmp(&a.exactness),
+    }
+}
+
+// Sorts `entries` by `rules[0]`, then recursively re-sorts each block of
+// entries tied under that rule by `rules[1..]`, and so on. Equivalent to a
+// lexicographic multi-key sort, but makes each tie-break step explicit.
+fn rank_by_rules(
+    mut entries: Vec<(HistoryEntry, RuleMetrics)>,
+    rules: &[RankingRule],
+) -> Vec<(HistoryEntry, RuleMetrics)> {
+    let Some((&rule, rest)) = rules.split_first() else {
+        return entries;
+    };
+    if entries.len() <= 1 {
+        return entries;
+    }
+
+    entries.sort_by(|a, b| compare_rule(rule, &a.1, &b.1));
+
+    let mut result = Vec::with_capacity(entries.len());
+    let mut bucket_start = 0;
+    for i in 1..=entries.len() {
+        let still_tied = i < entries.len()
+            && compare_rule(rule, &entries[i].1, &entries[bucket_start].1) == std::cmp::Ordering::Equal;
+        if still_tied {
+            continue;
+        }
+        let bucket: Vec<(HistoryEntry, RuleMetrics)> = entries[bucket_start..i].to_vec();
+        result.extend(rank_by_rules(bucket, rest));
+        bucket_start = i;
+    }
+    result
+}
+
+// General ranking engine: filters entries that match at least one keyword,
+// then orders them through `rules` as an ordered tie-break chain. An empty
+// `rules` list falls back to `DEFAULT_RANKING_RULES` so callers get a
+// sensible ranking without having to know the rule names.
+#[wasm_bindgen]
+pub fn rank_history(
+    entries: JsValue,
+    query: &str,
+    max_results: usize,
+    current_time: f64,
+    rules: JsValue,
+) -> Result<JsValue, JsValue> {
+    let entries: Vec<HistoryEntry> = serde_wasm_bindgen::from_value(entries)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse entries: {}", e)))?;
+
+    let rules: Vec<RankingRule> = if rules.is_undefined() || rules.is_null() {
+        Vec::new()
+    } else {
+        serde_wasm_bindgen::from_value(rules)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse ranking rules: {}", e)))?
+    };
+    let rules: &[RankingRule] = if rules.is_empty() { &DEFAULT_RANKING_RULES } else { &rules };
+
+    let keywords = extract_keywords(query);
+
+    if keywords.is_empty() {
+        let sorted_entries = serde_wasm_bindgen::to_value(&entries)?;
+        return sort_history_by_relevance(sorted_entries);
+    }
+
+    let candidates: Vec<(HistoryEntry, RuleMetrics)> = entries
+        .into_iter()
+        .map(|entry| {
+            let metrics = compute_rule_metrics(&entry, &keywords, current_time);
+            (entry, metrics)
+        })
+        .filter(|(_, metrics)| metrics.words_matched > 0)
+        .collect();
+
+    let ranked = rank_by_rules(candidates, rules);
+
+    let sorted: Vec<HistoryEntry> = ranked.into_iter().map(|(entry, _)| entry).take(max_results).collect();
+
+    serde_wasm_bindgen::to_value(&sorted)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
 }
 
 #[cfg(test)]
@@ -395,11 +1443,41 @@ mod tests {
         let keywords = extract_keywords("What did I visit about Rust programming?");
         assert!(keywords.contains(&"visit".to_string()));
         assert!(keywords.contains(&"rust".to_string()));
-        assert!(keywords.contains(&"programming".to_string()));
+        assert!(keywords.contains(&"program".to_string())); // stemmed from "programming"
         assert!(!keywords.contains(&"what".to_string())); // stop word
         assert!(!keywords.contains(&"did".to_string())); // stop word
     }
 
+    #[test]
+    fn test_extract_keywords_diacritic_folding() {
+        let keywords = extract_keywords("café con leche");
+        assert!(keywords.contains(&"cafe".to_string()));
+    }
+
+    #[test]
+    fn test_light_stem() {
+        assert_eq!(light_stem("running"), "run");
+        assert_eq!(light_stem("books"), "book");
+        assert_eq!(light_stem("studies"), "study");
+        assert_eq!(light_stem("rust"), "rust");
+    }
+
+    #[test]
+    fn test_normalize_tokens_keeps_short_non_latin_tokens() {
+        let tokens = normalize_tokens("日本語 is great");
+        assert!(tokens.iter().any(|t| t.chars().count() < MIN_LATIN_TOKEN_LEN));
+    }
+
+    #[test]
+    fn test_normalize_field_stems_document_text_like_queries() {
+        // "studies" stems to "study" via the "-ies" rule, which isn't a
+        // prefix of "studies" -- so this only matches if the document side
+        // is stemmed the same way the query side already is.
+        let keyword = extract_keywords("studies").into_iter().next().unwrap();
+        assert_eq!(keyword, "study");
+        assert!(normalize_field("Research Studies").contains(&keyword));
+    }
+
     #[test]
     fn test_extract_domain() {
         assert_eq!(extract_domain("https://example.com/path"), "example.com");
@@ -416,7 +1494,144 @@ mod tests {
             1234567890000.0,
         );
         let keywords = vec!["rust".to_string(), "programming".to_string()];
-        let score = calculate_relevance_score(&entry, &keywords, 1234567890000.0);
+        let score = calculate_relevance_score(&entry, &keywords, &[], 1234567890000.0, None);
         assert!(score > 0.0);
     }
+
+    #[test]
+    fn test_bounded_levenshtein() {
+        assert_eq!(bounded_levenshtein("python", "python", 2), Some(0));
+        assert_eq!(bounded_levenshtein("pyton", "python", 2), Some(1));
+        assert_eq!(bounded_levenshtein("pyton", "javascript", 2), None);
+    }
+
+    #[test]
+    fn test_fuzzy_match_typos() {
+        let config = FuzzyConfig::default();
+        assert_eq!(fuzzy_match_typos("python", "learning pyton today", &config), Some(1));
+        assert_eq!(fuzzy_match_typos("rust", "completely unrelated text", &config), None);
+    }
+
+    #[test]
+    fn test_parse_query_phrase() {
+        let parsed = parse_query("\"rust async\" book").unwrap();
+        assert_eq!(parsed.phrases, vec![vec!["rust".to_string(), "async".to_string()]]);
+        assert_eq!(parsed.keywords, vec!["book".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_query_unbalanced_quotes() {
+        assert!(parse_query("\"rust async book").is_err());
+    }
+
+    #[test]
+    fn test_phrase_matches_requires_contiguous_order() {
+        let phrase = vec!["rust".to_string(), "async".to_string()];
+        let field_tokens = tokenize_words("a book about rust async programming");
+        assert!(phrase_matches(&phrase, &field_tokens));
+
+        let out_of_order = tokenize_words("a book about async rust programming");
+        assert!(!phrase_matches(&phrase, &out_of_order));
+    }
+
+    #[test]
+    fn test_proximity_bonus_rewards_adjacent_keywords() {
+        let keywords = vec!["rust".to_string(), "async".to_string()];
+        let close = proximity_bonus(&keywords, "rust async book");
+        let far = proximity_bonus(&keywords, "rust is a language used by many teams for async work");
+        assert!(close > far);
+    }
+
+    #[test]
+    fn test_bm25_prefers_rare_keyword_match() {
+        let entries = vec![
+            HistoryEntry::new("https://a.com".to_string(), "Google Search".to_string(), 1, 0.0),
+            HistoryEntry::new("https://b.com".to_string(), "Google Rustlang".to_string(), 1, 0.0),
+            HistoryEntry::new("https://c.com".to_string(), "Google Maps".to_string(), 1, 0.0),
+        ];
+        let stats = Bm25Stats::build(&entries);
+
+        let common_only = calculate_bm25_score(&entries[0], &["google".to_string()], 0.0, &stats);
+        let rare_match = calculate_bm25_score(&entries[1], &["rustlang".to_string()], 0.0, &stats);
+        assert!(rare_match > common_only);
+    }
+
+    // Regression test for a bug where `sort_by_relevance_with_keywords`
+    // passed raw, unstemmed keywords to `calculate_bm25_score` while
+    // `document_tokens` (and thus `Bm25Stats`) indexes stemmed tokens, so a
+    // literal keyword like "programming" could never match a document
+    // containing "Programming" — `tf` silently came out as 0. Uses
+    // `normalize_keyword`, the same stemming `sort_by_relevance_with_keywords`
+    // now applies before scoring.
+    #[test]
+    fn test_bm25_matches_stemmed_keyword() {
+        let entries = vec![
+            HistoryEntry::new("https://a.com".to_string(), "Programming Basics".to_string(), 1, 0.0),
+            HistoryEntry::new("https://b.com".to_string(), "Cooking Basics".to_string(), 1, 0.0),
+        ];
+        let stats = Bm25Stats::build(&entries);
+        let keywords: Vec<String> = ["programming"].iter().map(|k| normalize_keyword(k)).collect();
+
+        // The visit/recency bonus alone is positive, so compare against it:
+        // only a non-zero BM25 term contribution lifts the score above it.
+        let score = calculate_bm25_score(&entries[0], &keywords, 0.0, &stats);
+        assert!(score > visit_and_recency_bonus(&entries[0], 0.0));
+    }
+
+    #[test]
+    fn test_registrable_domain() {
+        assert_eq!(registrable_domain("docs.rust-lang.org"), "rust-lang.org");
+        assert_eq!(registrable_domain("rust-lang.org"), "rust-lang.org");
+        assert_eq!(registrable_domain("localhost"), "localhost");
+    }
+
+    #[test]
+    fn test_dedupe_by_distinct_collapses_same_domain() {
+        let entries = vec![
+            HistoryEntry::new("https://docs.rust-lang.org/book".to_string(), "Book".to_string(), 5, 3.0),
+            HistoryEntry::new("https://docs.rust-lang.org/std".to_string(), "Std".to_string(), 4, 2.0),
+            HistoryEntry::new("https://other.com".to_string(), "Other".to_string(), 1, 1.0),
+        ];
+
+        let (deduped, suppressed) = dedupe_by_distinct(entries, DistinctKey::Domain, 10);
+        assert_eq!(deduped.len(), 2);
+        assert_eq!(suppressed.get("docs.rust-lang.org"), Some(&1));
+    }
+
+    #[test]
+    fn test_terms_drop_order_last_drops_from_the_end() {
+        let keywords = vec!["rust".to_string(), "async".to_string(), "book".to_string()];
+        let order = terms_drop_order(&keywords, TermsMatchingStrategy::Last, &[]);
+        assert_eq!(order, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_terms_drop_order_frequency_prefers_most_common_term() {
+        let entries = vec![
+            HistoryEntry::new("https://a.com".to_string(), "Rust Async Book".to_string(), 1, 0.0),
+            HistoryEntry::new("https://b.com".to_string(), "Rust Ownership".to_string(), 1, 0.0),
+            HistoryEntry::new("https://c.com".to_string(), "Rust Traits".to_string(), 1, 0.0),
+        ];
+        let keywords = vec!["rust".to_string(), "async".to_string()];
+        let order = terms_drop_order(&keywords, TermsMatchingStrategy::Frequency, &entries);
+        // "rust" appears in every document, "async" in only one, so "rust" drops first.
+        assert_eq!(order[0], 0);
+    }
+
+    #[test]
+    fn test_find_relevant_history_graded_falls_back_when_all_terms_dont_match() {
+        let entries = vec![
+            HistoryEntry::new("https://a.com".to_string(), "Rust Async Book".to_string(), 1, 0.0),
+            HistoryEntry::new("https://b.com".to_string(), "Rust Ownership Guide".to_string(), 1, 0.0),
+        ];
+        let parsed = parse_query("rust async ownership").unwrap();
+
+        let graded = graded_matches_internal(entries, &parsed, 10, 0.0, TermsMatchingStrategy::Last)
+            .expect("query has keywords, so this must not fall back to plain sorting");
+
+        // No entry matches all three keywords, so the fallback must kick in
+        // and still return both entries, tagged with how many terms they hit.
+        assert_eq!(graded.len(), 2);
+        assert!(graded.iter().all(|g| g.matched_terms >= 1));
+    }
 }